@@ -2,12 +2,23 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::fs;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::Write;
 use std::path::PathBuf;
 
+// Number of rotated backups kept per character (character.json.bak.1 is the most recent)
+const MAX_BACKUPS: u32 = 5;
+
+// The schema version written by this build. Stored files older than this are
+// migrated up to it in `load_characters` before being deserialized.
+const CURRENT_VERSION: u32 = 1;
+
 #[derive(Serialize, Deserialize)]
 pub struct Character {
+    #[serde(default)]
+    pub schema_version: u32,
     pub id: String,
     pub name: String,
     pub race: String,
@@ -15,7 +26,6 @@ pub struct Character {
     pub subrace: Option<String>,
     pub class: String,
     pub background: String,
-    #[serde(default = "default_alignment")]
     pub alignment: String,
     pub level: u8,
     pub ability_scores: AbilityScores,
@@ -60,7 +70,6 @@ pub struct Currency {
     pub copper: u32,
 }
 
-// Default alignment for older characters that don't have the field
 fn default_alignment() -> String {
     "Neutral".to_string()
 }
@@ -75,7 +84,351 @@ pub struct AbilityScores {
     pub charisma: u8,
 }
 
-// Get the characters directory path
+// Schema migrations, one function per version bump. Each takes the character
+// as a raw JSON value and mutates it in place so that renamed/added fields
+// are handled here instead of scattered across `Character` as ad hoc
+// `#[serde(default)]`s.
+fn migrate_v0_to_v1(value: &mut serde_json::Value) {
+    if let Some(obj) = value.as_object_mut() {
+        obj.entry("alignment").or_insert_with(|| json!(default_alignment()));
+        obj.insert("schema_version".to_string(), json!(1));
+    }
+}
+
+// Run every migration needed to bring `value` up to `CURRENT_VERSION`.
+// Returns whether any migration actually ran.
+fn migrate_to_current(value: &mut serde_json::Value) -> Result<bool, String> {
+    if !value.is_object() {
+        return Err("Character file does not contain a JSON object".to_string());
+    }
+
+    let mut version = value
+        .get("schema_version")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as u32;
+    let starting_version = version;
+
+    while version < CURRENT_VERSION {
+        match version {
+            0 => migrate_v0_to_v1(value),
+            _ => break,
+        }
+
+        let next_version = value
+            .get("schema_version")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(version) as u32;
+
+        // A migration must always advance the version; otherwise we'd spin
+        // forever on a file a migration function doesn't know how to handle.
+        if next_version <= version {
+            return Err(format!(
+                "Migration from schema version {} made no progress",
+                version
+            ));
+        }
+        version = next_version;
+    }
+
+    Ok(version > starting_version)
+}
+
+// D&D Beyond's stat ids, in the order they line up with `AbilityScores`
+const DNDBEYOND_STAT_IDS: [(&str, u64); 6] = [
+    ("strength", 1),
+    ("dexterity", 2),
+    ("constitution", 3),
+    ("intelligence", 4),
+    ("wisdom", 5),
+    ("charisma", 6),
+];
+
+const SKILL_NAMES: [&str; 18] = [
+    "acrobatics",
+    "animal-handling",
+    "arcana",
+    "athletics",
+    "deception",
+    "history",
+    "insight",
+    "intimidation",
+    "investigation",
+    "medicine",
+    "nature",
+    "perception",
+    "performance",
+    "persuasion",
+    "religion",
+    "sleight-of-hand",
+    "stealth",
+    "survival",
+];
+
+// Sum of an ability's entry across the base/bonus/override stat arrays, where
+// an override (when present and non-null) replaces the base+bonus total.
+fn dndbeyond_stat_value(data: &serde_json::Value, stat_id: u64) -> u8 {
+    let lookup = |array: &str| -> Option<i64> {
+        data.get(array)?
+            .as_array()?
+            .iter()
+            .find(|entry| entry.get("id").and_then(|v| v.as_u64()) == Some(stat_id))
+            .and_then(|entry| entry.get("value"))
+            .and_then(|v| v.as_i64())
+    };
+
+    if let Some(override_value) = lookup("overrideStats") {
+        return override_value.clamp(1, 30) as u8;
+    }
+
+    let base = lookup("stats").unwrap_or(10);
+    let bonus = lookup("bonusStats").unwrap_or(0);
+    (base + bonus).clamp(1, 30) as u8
+}
+
+fn dndbeyond_ability_scores(data: &serde_json::Value) -> AbilityScores {
+    let mut scores = HashMap::new();
+    for (ability, stat_id) in DNDBEYOND_STAT_IDS {
+        scores.insert(ability, dndbeyond_stat_value(data, stat_id));
+    }
+
+    AbilityScores {
+        strength: scores["strength"],
+        dexterity: scores["dexterity"],
+        constitution: scores["constitution"],
+        intelligence: scores["intelligence"],
+        wisdom: scores["wisdom"],
+        charisma: scores["charisma"],
+    }
+}
+
+fn dndbeyond_classes_and_level(data: &serde_json::Value) -> (String, u8) {
+    let classes = data
+        .get("classes")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    let total_level: u64 = classes
+        .iter()
+        .filter_map(|c| c.get("level").and_then(|v| v.as_u64()))
+        .sum();
+
+    let class_name = classes
+        .iter()
+        .filter_map(|c| {
+            let name = c.get("definition")?.get("name")?.as_str()?;
+            let level = c.get("level").and_then(|v| v.as_u64()).unwrap_or(0);
+            Some(format!("{} {}", name, level))
+        })
+        .collect::<Vec<_>>()
+        .join(" / ");
+
+    (class_name, total_level.clamp(1, 20) as u8)
+}
+
+fn dndbeyond_race(data: &serde_json::Value) -> (String, Option<String>) {
+    let race = data.get("race");
+    let full_name = race
+        .and_then(|r| r.get("fullName"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("Unknown")
+        .to_string();
+    let subrace = race
+        .and_then(|r| r.get("subRaceShortName"))
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+
+    (full_name, subrace)
+}
+
+// Proficiency bonus for a given total character level, per the 5e progression
+fn proficiency_bonus(level: u8) -> u8 {
+    2 + (level.saturating_sub(1)) / 4
+}
+
+struct Proficiencies {
+    saving_throws: HashMap<String, u8>,
+    skills: HashMap<String, u8>,
+    armor: Vec<String>,
+    weapons: Vec<String>,
+    tools: Vec<String>,
+    languages: Vec<String>,
+}
+
+// Walk every modifier list in `modifiers` (one array per source: race, class,
+// background, feat, item, ...) and sort entries of type "proficiency" into
+// the Character's proficiency fields.
+fn dndbeyond_collect_proficiencies(data: &serde_json::Value, prof_bonus: u8) -> Proficiencies {
+    let mut result = Proficiencies {
+        saving_throws: HashMap::new(),
+        skills: HashMap::new(),
+        armor: Vec::new(),
+        weapons: Vec::new(),
+        tools: Vec::new(),
+        languages: Vec::new(),
+    };
+
+    let modifiers = match data.get("modifiers").and_then(|m| m.as_object()) {
+        Some(m) => m,
+        None => return result,
+    };
+
+    for entries in modifiers.values() {
+        let entries = match entries.as_array() {
+            Some(e) => e,
+            None => continue,
+        };
+
+        for entry in entries {
+            if entry.get("type").and_then(|v| v.as_str()) != Some("proficiency") {
+                continue;
+            }
+
+            let sub_type = entry
+                .get("subType")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string();
+            let friendly_name = entry
+                .get("friendlySubtypeName")
+                .and_then(|v| v.as_str())
+                .unwrap_or(&sub_type)
+                .to_string();
+
+            if let Some(ability) = sub_type.strip_suffix("-saving-throws") {
+                result
+                    .saving_throws
+                    .insert(ability.to_string(), prof_bonus);
+            } else if SKILL_NAMES.contains(&sub_type.as_str()) {
+                result.skills.insert(sub_type, prof_bonus);
+            } else if friendly_name.to_lowercase().contains("armor") {
+                result.armor.push(friendly_name);
+            } else if friendly_name.to_lowercase().contains("weapon") {
+                result.weapons.push(friendly_name);
+            } else if friendly_name.to_lowercase().contains("tool")
+                || friendly_name.to_lowercase().contains("kit")
+                || friendly_name.to_lowercase().contains("supplies")
+            {
+                result.tools.push(friendly_name);
+            } else {
+                result.languages.push(friendly_name);
+            }
+        }
+    }
+
+    result
+}
+
+fn dndbeyond_currency(data: &serde_json::Value) -> Currency {
+    let currencies = data.get("currencies");
+    let field = |name: &str| -> u32 {
+        currencies
+            .and_then(|c| c.get(name))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as u32
+    };
+
+    Currency {
+        platinum: field("pp"),
+        gold: field("gp"),
+        silver: field("sp"),
+        copper: field("cp"),
+    }
+}
+
+fn dndbeyond_hit_points(data: &serde_json::Value) -> (u16, u16) {
+    let get = |name: &str| -> i64 {
+        data.get(name).and_then(|v| v.as_i64()).unwrap_or(0)
+    };
+
+    let max_hp = (get("baseHitPoints") + get("bonusHitPoints")).clamp(1, u16::MAX as i64);
+    let removed = get("removedHitPoints").max(0);
+    let current_hp = (max_hp - removed).clamp(0, u16::MAX as i64);
+
+    (current_hp as u16, max_hp as u16)
+}
+
+fn dndbeyond_armor_class(data: &serde_json::Value, dexterity: u8) -> u8 {
+    let dex_modifier = (dexterity as i32 - 10).div_euclid(2);
+
+    let bonus: i64 = data
+        .get("modifiers")
+        .and_then(|m| m.as_object())
+        .into_iter()
+        .flat_map(|m| m.values())
+        .filter_map(|entries| entries.as_array())
+        .flatten()
+        .filter(|entry| entry.get("subType").and_then(|v| v.as_str()) == Some("armor-class"))
+        .filter_map(|entry| entry.get("value").and_then(|v| v.as_i64()))
+        .sum();
+
+    (10 + dex_modifier + bonus as i32).clamp(0, 30) as u8
+}
+
+// Import a character exported from D&D Beyond's character JSON format
+#[tauri::command]
+fn import_dndbeyond(json: String) -> Result<Character, String> {
+    let raw: serde_json::Value =
+        serde_json::from_str(&json).map_err(|e| format!("Failed to parse JSON: {}", e))?;
+
+    // Some exports wrap the character under a top-level "data" key
+    let data = raw.get("data").unwrap_or(&raw);
+
+    let name = data
+        .get("name")
+        .and_then(|v| v.as_str())
+        .unwrap_or("Unnamed Character")
+        .to_string();
+
+    let ability_scores = dndbeyond_ability_scores(data);
+    let (class, level) = dndbeyond_classes_and_level(data);
+    let (race, subrace) = dndbeyond_race(data);
+    let background = data
+        .get("background")
+        .and_then(|b| b.get("definition"))
+        .and_then(|d| d.get("name"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+
+    let prof_bonus = proficiency_bonus(level);
+    let proficiencies = dndbeyond_collect_proficiencies(data, prof_bonus);
+    let (hit_points, max_hit_points) = dndbeyond_hit_points(data);
+    let armor_class = dndbeyond_armor_class(data, ability_scores.dexterity);
+    let dex_modifier = (ability_scores.dexterity as i32 - 10).div_euclid(2);
+
+    Ok(Character {
+        schema_version: CURRENT_VERSION,
+        id: uuid::Uuid::new_v4().to_string(),
+        name,
+        race,
+        subrace,
+        class,
+        background,
+        alignment: default_alignment(),
+        level,
+        ability_scores,
+        hit_points,
+        max_hit_points: Some(max_hit_points),
+        armor_class,
+        initiative: dex_modifier as i8,
+        equipment: Vec::new(),
+        spells: Vec::new(),
+        spell_slots: Vec::new(),
+        notes: String::new(),
+        saving_throw_proficiencies: proficiencies.saving_throws,
+        skill_proficiencies: proficiencies.skills,
+        armor_proficiencies: proficiencies.armor,
+        weapon_proficiencies: proficiencies.weapons,
+        tool_proficiencies: proficiencies.tools,
+        languages: proficiencies.languages,
+        heroic_inspiration: false,
+        used_abilities: Vec::new(),
+        currency: Some(dndbeyond_currency(data)),
+    })
+}
+
+// Get the characters directory path (the primary storage root)
 fn get_characters_dir() -> PathBuf {
     let mut path = dirs::data_dir().unwrap();
     path.push("dnd-beyond-desktop");
@@ -83,6 +436,56 @@ fn get_characters_dir() -> PathBuf {
     path
 }
 
+// Where app settings (including configured storage roots) are persisted
+fn get_settings_path() -> PathBuf {
+    let mut path = dirs::data_dir().unwrap();
+    path.push("dnd-beyond-desktop");
+    path.push("settings.json");
+    path
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Settings {
+    #[serde(default)]
+    pub storage_roots: Vec<String>,
+}
+
+// Load persisted settings, falling back to defaults if none exist yet
+fn load_settings() -> Settings {
+    let path = get_settings_path();
+    if !path.exists() {
+        return Settings::default();
+    }
+
+    fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_settings(settings: &Settings) -> std::io::Result<()> {
+    if let Some(parent) = get_settings_path().parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let content = serde_json::to_string_pretty(settings)?;
+    fs::write(get_settings_path(), content)
+}
+
+// All configured storage roots, with the primary (default data dir) root always first
+fn get_storage_roots() -> Vec<PathBuf> {
+    let primary = get_characters_dir();
+    let settings = load_settings();
+
+    let mut roots = vec![primary.clone()];
+    for root in settings.storage_roots {
+        let path = PathBuf::from(root);
+        if path != primary && !roots.contains(&path) {
+            roots.push(path);
+        }
+    }
+    roots
+}
+
 // Create characters directory if it doesn't exist
 fn ensure_characters_dir() -> std::io::Result<()> {
     let dir = get_characters_dir();
@@ -90,28 +493,115 @@ fn ensure_characters_dir() -> std::io::Result<()> {
     Ok(())
 }
 
-// Load all characters from the characters directory
-#[tauri::command]
-fn load_characters() -> Result<Vec<Character>, String> {
-    let dir = get_characters_dir();
-    
+// Reject anything that isn't a real, existing directory outside of a
+// filesystem root or the user's home directory outright. `allow_directory`
+// is recursive, so handing it an unvalidated frontend string (or a typo'd
+// settings value) would grant the fs plugin unrestricted access — the exact
+// risk this capability/scope model exists to close. Callers should only ever
+// reach this with a path the user explicitly chose, e.g. via a folder
+// picker, not a raw IPC argument.
+fn validate_scope_path(path: &PathBuf) -> Result<PathBuf, String> {
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| format!("Invalid directory {}: {}", path.display(), e))?;
+
+    if !canonical.is_dir() {
+        return Err(format!("Invalid directory {}: not a directory", canonical.display()));
+    }
+
+    if canonical.parent().is_none() {
+        return Err(format!(
+            "Refusing to grant filesystem access to root directory {}",
+            canonical.display()
+        ));
+    }
+
+    if let Some(home) = dirs::home_dir() {
+        if canonical == home {
+            return Err("Refusing to grant filesystem access to the home directory".to_string());
+        }
+    }
+
+    Ok(canonical)
+}
+
+// Grant the fs plugin access to every configured storage root. Called at
+// startup and whenever the storage roots change, so the frontend's fs scope
+// never extends beyond character storage plus whatever export dir the user
+// explicitly chooses (see `register_export_scope`).
+//
+// The primary root is required, so failing to scope it is fatal. Secondary
+// roots (e.g. a Dropbox/OneDrive folder set in `settings.json`) can go
+// missing if the drive isn't mounted yet at launch; rather than crash the
+// whole app over one unreachable extra root, skip it and warn so the user
+// can still reach their primary characters.
+fn register_storage_scope(app: &tauri::AppHandle) -> Result<(), String> {
+    use tauri_plugin_fs::FsExt;
+
+    ensure_characters_dir().map_err(|e| format!("Failed to create directory: {}", e))?;
+
+    let mut roots = get_storage_roots().into_iter();
+    let primary = roots.next().expect("get_storage_roots always includes the primary root");
+    let validated = validate_scope_path(&primary)?;
+    app.fs_scope()
+        .allow_directory(&validated, true)
+        .map_err(|e| format!("Failed to scope {}: {}", validated.display(), e))?;
+
+    for root in roots {
+        match validate_scope_path(&root) {
+            Ok(validated) => {
+                if let Err(e) = app.fs_scope().allow_directory(&validated, true) {
+                    eprintln!("Warning: failed to scope storage root {}: {}", validated.display(), e);
+                }
+            }
+            Err(e) => eprintln!("Warning: skipping unreachable storage root {}: {}", root.display(), e),
+        }
+    }
+    Ok(())
+}
+
+// Parse a character file as a generic Value, migrate it up to
+// `CURRENT_VERSION`, then deserialize into `Character`. Returns whether the
+// file was upgraded so callers can decide to persist the migrated form.
+fn load_and_migrate_character(content: &str) -> Result<(Character, bool), String> {
+    let mut value: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| format!("Failed to parse character: {}", e))?;
+
+    let upgraded = migrate_to_current(&mut value)?;
+
+    let character: Character = serde_json::from_value(value)
+        .map_err(|e| format!("Failed to parse character: {}", e))?;
+
+    Ok((character, upgraded))
+}
+
+// Read every character JSON file out of a single storage root, migrating and
+// re-saving any that were still on an older schema version.
+fn load_characters_from(dir: &PathBuf) -> Result<Vec<Character>, String> {
     if !dir.exists() {
         return Ok(Vec::new());
     }
 
     let mut characters = Vec::new();
-    
-    for entry in fs::read_dir(&dir).map_err(|e| format!("Failed to read directory: {}", e))? {
+
+    for entry in fs::read_dir(dir).map_err(|e| format!("Failed to read directory: {}", e))? {
         let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
         let path = entry.path();
-        
+
         if path.extension().and_then(|s| s.to_str()) == Some("json") {
             let content = fs::read_to_string(&path)
                 .map_err(|e| format!("Failed to read file {}: {}", path.display(), e))?;
-            
-            let character: Character = serde_json::from_str(&content)
-                .map_err(|e| format!("Failed to parse character: {}", e))?;
-            
+
+            let (character, upgraded) = load_and_migrate_character(&content)?;
+
+            if upgraded {
+                let migrated_content = serde_json::to_string_pretty(&character)
+                    .map_err(|e| format!("Failed to serialize migrated character: {}", e))?;
+                atomic_write(&path, &migrated_content).map_err(|e| {
+                    format!("Failed to re-save migrated character {}: {}", path.display(), e)
+                })?;
+            }
+
             characters.push(character);
         }
     }
@@ -119,60 +609,668 @@ fn load_characters() -> Result<Vec<Character>, String> {
     Ok(characters)
 }
 
-// Save a character to file
+// Load all characters from every configured storage root, deduping by id.
+// The primary root is scanned first, so it wins on id conflicts.
+#[tauri::command]
+fn load_characters() -> Result<Vec<Character>, String> {
+    let mut characters = Vec::new();
+    let mut seen_ids = HashSet::new();
+
+    for dir in get_storage_roots() {
+        for character in load_characters_from(&dir)? {
+            if seen_ids.insert(character.id.clone()) {
+                characters.push(character);
+            }
+        }
+    }
+
+    Ok(characters)
+}
+
+// Character ids are generated by us as UUIDs, but they flow in over IPC from
+// the frontend on every save/delete/backup command, so reject anything other
+// than ASCII letters, digits and hyphens (the characters a UUID is made of)
+// before it's used to build a filesystem path. The full UUID format is not
+// checked. This blocks path traversal (e.g. an id of "../../etc/passwd").
+fn validate_character_id(id: &str) -> Result<(), String> {
+    if !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        Ok(())
+    } else {
+        Err(format!("Invalid character id: {}", id))
+    }
+}
+
+// Path to the nth rotated backup of a character file (1 = most recent)
+fn backup_path(dir: &PathBuf, id: &str, index: u32) -> PathBuf {
+    dir.join(format!("{}.json.bak.{}", id, index))
+}
+
+// Find which configured storage root actually holds a character's file (live
+// or backup), since `load_characters` surfaces characters from every root but
+// `save_character` only ever writes new ones to the primary root.
+fn find_character_root(id: &str) -> Option<PathBuf> {
+    get_storage_roots().into_iter().find(|dir| {
+        dir.join(format!("{}.json", id)).exists()
+            || (1..=MAX_BACKUPS).any(|index| backup_path(dir, id, index).exists())
+    })
+}
+
+// Shift existing backups up one slot, dropping anything past MAX_BACKUPS, then
+// move the current live file (if any) into the now-free bak.1 slot.
+fn rotate_backups(dir: &PathBuf, id: &str) -> std::io::Result<()> {
+    for index in (1..MAX_BACKUPS).rev() {
+        let from = backup_path(dir, id, index);
+        if from.exists() {
+            fs::rename(&from, backup_path(dir, id, index + 1))?;
+        }
+    }
+
+    let live = dir.join(format!("{}.json", id));
+    if live.exists() {
+        fs::rename(&live, backup_path(dir, id, 1))?;
+    }
+
+    Ok(())
+}
+
+// Serialize content into a temp file next to `path` and fsync it, without
+// touching `path` itself. Kept separate from the rename step so callers can
+// rotate backups in between: if the write fails, the live file (and its
+// backups) are never disturbed.
+fn write_temp_file(path: &PathBuf, content: &str) -> std::io::Result<PathBuf> {
+    let tmp_path = path.with_extension("json.tmp");
+
+    let mut tmp_file = File::create(&tmp_path)?;
+    tmp_file.write_all(content.as_bytes())?;
+    tmp_file.sync_all()?;
+
+    Ok(tmp_path)
+}
+
+// Write content to `path` atomically: serialize to a temp file in the same
+// directory, fsync it, then rename over the target so a crash mid-write
+// can't leave a corrupt or partial file behind.
+fn atomic_write(path: &PathBuf, content: &str) -> std::io::Result<()> {
+    let tmp_path = write_temp_file(path, content)?;
+    fs::rename(&tmp_path, path)
+}
+
+// Save a character to file in the primary storage root, backing up whatever
+// version was previously on disk. The new content is written and fsynced to
+// a temp file *before* backups are rotated, so a failed write never costs us
+// the last-known-good copy.
 #[tauri::command]
 fn save_character(character: Character) -> Result<(), String> {
+    validate_character_id(&character.id)?;
     ensure_characters_dir().map_err(|e| format!("Failed to create directory: {}", e))?;
-    
+
     let dir = get_characters_dir();
-    let filename = format!("{}.json", character.id);
-    let path = dir.join(&filename);
-    
+    let path = dir.join(format!("{}.json", character.id));
+
     let content = serde_json::to_string_pretty(&character)
         .map_err(|e| format!("Failed to serialize character: {}", e))?;
-    
-    fs::write(&path, content)
-        .map_err(|e| format!("Failed to write file: {}", e))?;
-    
+
+    let tmp_path =
+        write_temp_file(&path, &content).map_err(|e| format!("Failed to write file: {}", e))?;
+
+    rotate_backups(&dir, &character.id)
+        .map_err(|e| format!("Failed to rotate backups: {}", e))?;
+
+    fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to write file: {}", e))?;
+
     Ok(())
 }
 
-// Delete a character file
+// List the backup indices (1 = most recent) available for a character
+#[tauri::command]
+fn list_character_backups(id: String) -> Result<Vec<u32>, String> {
+    validate_character_id(&id)?;
+    let dir = find_character_root(&id).unwrap_or_else(get_characters_dir);
+    let mut indices: Vec<u32> = (1..=MAX_BACKUPS)
+        .filter(|index| backup_path(&dir, &id, *index).exists())
+        .collect();
+    indices.sort_unstable();
+    Ok(indices)
+}
+
+// Restore a character from one of its rotated backups. The restored content
+// is written and fsynced to a temp file first, then the file currently on
+// disk is rotated into the backups, then the temp file is renamed into place
+// — so a failed restore never costs us the live file either.
+#[tauri::command]
+fn restore_character_backup(id: String, index: u32) -> Result<Character, String> {
+    validate_character_id(&id)?;
+    let dir = find_character_root(&id)
+        .ok_or_else(|| format!("Character {} not found in any storage root", id))?;
+    let backup = backup_path(&dir, &id, index);
+
+    let content = fs::read_to_string(&backup)
+        .map_err(|e| format!("Failed to read backup {}: {}", index, e))?;
+
+    let (character, _upgraded) = load_and_migrate_character(&content)?;
+    let content = serde_json::to_string_pretty(&character)
+        .map_err(|e| format!("Failed to serialize character: {}", e))?;
+
+    let path = dir.join(format!("{}.json", id));
+    let tmp_path =
+        write_temp_file(&path, &content).map_err(|e| format!("Failed to write file: {}", e))?;
+
+    rotate_backups(&dir, &id).map_err(|e| format!("Failed to rotate backups: {}", e))?;
+
+    fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to write file: {}", e))?;
+
+    Ok(character)
+}
+
+// Delete a character file, wherever among the configured storage roots it
+// actually lives
 #[tauri::command]
 fn delete_character(id: String) -> Result<(), String> {
-    let dir = get_characters_dir();
-    let filename = format!("{}.json", id);
-    let path = dir.join(&filename);
-    
+    validate_character_id(&id)?;
+    let Some(dir) = find_character_root(&id) else {
+        return Ok(());
+    };
+    let path = dir.join(format!("{}.json", id));
+
     if path.exists() {
         fs::remove_file(&path)
             .map_err(|e| format!("Failed to delete file: {}", e))?;
     }
-    
+
     Ok(())
 }
 
+// Get the current settings (including configured storage roots)
+#[tauri::command]
+fn get_settings() -> Result<Settings, String> {
+    Ok(load_settings())
+}
+
+// Replace the list of additional storage roots scanned alongside the primary root
+#[tauri::command]
+fn set_storage_roots(paths: Vec<String>, app: tauri::AppHandle) -> Result<(), String> {
+    for path in &paths {
+        validate_scope_path(&PathBuf::from(path))?;
+    }
+
+    let settings = Settings {
+        storage_roots: paths,
+    };
+    save_settings(&settings).map_err(|e| format!("Failed to save settings: {}", e))?;
+    register_storage_scope(&app)
+}
+
+// Allow the fs plugin to read/write a user-chosen export directory. Called
+// once the user has picked a destination folder for `export_character`/
+// `export_all`, so arbitrary-path writes stay denied by default.
+#[tauri::command]
+fn register_export_scope(path: String, app: tauri::AppHandle) -> Result<(), String> {
+    use tauri_plugin_fs::FsExt;
+
+    let validated = validate_scope_path(&PathBuf::from(&path))?;
+    app.fs_scope()
+        .allow_directory(&validated, true)
+        .map_err(|e| format!("Failed to scope export directory: {}", e))
+}
+
 // Get the characters directory path for export
 #[tauri::command]
 fn get_characters_directory() -> Result<String, String> {
     Ok(get_characters_dir().to_string_lossy().to_string())
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, clap::ValueEnum)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportFormat {
+    Json,
+    Markdown,
+    Pdf,
+    FoundryVtt,
+}
+
+impl ExportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Pdf => "pdf",
+            ExportFormat::FoundryVtt => "json",
+        }
+    }
+}
+
+fn find_character(id: &str) -> Result<Character, String> {
+    load_characters()?
+        .into_iter()
+        .find(|c| c.id == id)
+        .ok_or_else(|| format!("Character not found: {}", id))
+}
+
+// Render a readable Markdown statblock for a character
+fn character_to_markdown(character: &Character) -> String {
+    let mut md = String::new();
+
+    md.push_str(&format!("# {}\n\n", character.name));
+    let subrace = character
+        .subrace
+        .as_ref()
+        .map(|s| format!(" ({})", s))
+        .unwrap_or_default();
+    md.push_str(&format!(
+        "*Level {} {}{} {}, {}*\n\n",
+        character.level, character.race, subrace, character.class, character.background
+    ));
+    md.push_str(&format!("**Alignment:** {}\n\n", character.alignment));
+
+    md.push_str("## Ability Scores\n\n");
+    md.push_str("| STR | DEX | CON | INT | WIS | CHA |\n");
+    md.push_str("|---|---|---|---|---|---|\n");
+    md.push_str(&format!(
+        "| {} | {} | {} | {} | {} | {} |\n\n",
+        character.ability_scores.strength,
+        character.ability_scores.dexterity,
+        character.ability_scores.constitution,
+        character.ability_scores.intelligence,
+        character.ability_scores.wisdom,
+        character.ability_scores.charisma
+    ));
+
+    let max_hp = character.max_hit_points.unwrap_or(character.hit_points);
+    md.push_str(&format!(
+        "**HP:** {}/{}  **AC:** {}  **Initiative:** {:+}\n\n",
+        character.hit_points, max_hp, character.armor_class, character.initiative
+    ));
+
+    if !character.saving_throw_proficiencies.is_empty() {
+        md.push_str("## Saving Throw Proficiencies\n\n");
+        for (ability, bonus) in &character.saving_throw_proficiencies {
+            md.push_str(&format!("- {}: +{}\n", ability, bonus));
+        }
+        md.push('\n');
+    }
+
+    if !character.skill_proficiencies.is_empty() {
+        md.push_str("## Skill Proficiencies\n\n");
+        for (skill, bonus) in &character.skill_proficiencies {
+            md.push_str(&format!("- {}: +{}\n", skill, bonus));
+        }
+        md.push('\n');
+    }
+
+    if !character.equipment.is_empty() {
+        md.push_str("## Equipment\n\n");
+        for item in &character.equipment {
+            md.push_str(&format!("- {}\n", item));
+        }
+        md.push('\n');
+    }
+
+    if !character.spells.is_empty() {
+        md.push_str("## Spells\n\n");
+        for spell in &character.spells {
+            md.push_str(&format!("- {}\n", spell));
+        }
+        md.push('\n');
+    }
+
+    if let Some(currency) = &character.currency {
+        md.push_str(&format!(
+            "**Currency:** {}pp {}gp {}sp {}cp\n\n",
+            currency.platinum, currency.gold, currency.silver, currency.copper
+        ));
+    }
+
+    if !character.notes.is_empty() {
+        md.push_str("## Notes\n\n");
+        md.push_str(&character.notes);
+        md.push('\n');
+    }
+
+    md
+}
+
+// Build a FoundryVTT actor JSON document for a character
+fn character_to_foundry_vtt(character: &Character) -> serde_json::Value {
+    let max_hp = character.max_hit_points.unwrap_or(character.hit_points);
+
+    let mut items: Vec<serde_json::Value> = character
+        .equipment
+        .iter()
+        .map(|name| json!({ "name": name, "type": "equipment" }))
+        .collect();
+    items.extend(
+        character
+            .spells
+            .iter()
+            .map(|name| json!({ "name": name, "type": "spell" })),
+    );
+
+    json!({
+        "name": character.name,
+        "type": "character",
+        "system": {
+            "abilities": {
+                "str": { "value": character.ability_scores.strength },
+                "dex": { "value": character.ability_scores.dexterity },
+                "con": { "value": character.ability_scores.constitution },
+                "int": { "value": character.ability_scores.intelligence },
+                "wis": { "value": character.ability_scores.wisdom },
+                "cha": { "value": character.ability_scores.charisma },
+            },
+            "attributes": {
+                "hp": { "value": character.hit_points, "max": max_hp },
+                "ac": { "value": character.armor_class },
+                "init": { "value": character.initiative },
+            },
+            "details": {
+                "race": character.race,
+                "background": character.background,
+                "alignment": character.alignment,
+                "level": character.level,
+            },
+            "currency": character.currency.as_ref().map(|c| json!({
+                "pp": c.platinum,
+                "gp": c.gold,
+                "sp": c.silver,
+                "cp": c.copper,
+            })),
+            "traits": {
+                "languages": character.languages,
+            },
+        },
+        "items": items,
+    })
+}
+
+// Fill out a basic 5e character sheet PDF with the character's core stats
+fn character_to_pdf_bytes(character: &Character) -> Result<Vec<u8>, String> {
+    use printpdf::{BuiltinFont, Mm, PdfDocument};
+
+    let (doc, page, layer) =
+        PdfDocument::new(&character.name, Mm(210.0), Mm(297.0), "Layer 1");
+    let font = doc
+        .add_builtin_font(BuiltinFont::Helvetica)
+        .map_err(|e| format!("Failed to load PDF font: {}", e))?;
+    let layer = doc.get_page(page).get_layer(layer);
+
+    let max_hp = character.max_hit_points.unwrap_or(character.hit_points);
+    let subrace = character
+        .subrace
+        .as_ref()
+        .map(|s| format!(" ({})", s))
+        .unwrap_or_default();
+
+    let mut y = Mm(280.0);
+    let mut write_line = |text: &str| {
+        layer.use_text(text, 11.0, Mm(15.0), y, &font);
+        y.0 -= 6.0;
+    };
+
+    write_line(&character.name);
+    write_line(&format!(
+        "Level {} {}{} {}",
+        character.level, character.race, subrace, character.class
+    ));
+    write_line(&format!(
+        "Background: {}   Alignment: {}",
+        character.background, character.alignment
+    ));
+    write_line(&format!(
+        "STR {}  DEX {}  CON {}  INT {}  WIS {}  CHA {}",
+        character.ability_scores.strength,
+        character.ability_scores.dexterity,
+        character.ability_scores.constitution,
+        character.ability_scores.intelligence,
+        character.ability_scores.wisdom,
+        character.ability_scores.charisma
+    ));
+    write_line(&format!(
+        "HP {}/{}   AC {}   Initiative {:+}",
+        character.hit_points, max_hp, character.armor_class, character.initiative
+    ));
+    write_line(&format!(
+        "Proficiencies: {}",
+        character
+            .skill_proficiencies
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ")
+    ));
+    write_line(&format!("Spells: {}", character.spells.join(", ")));
+
+    let mut bytes: Vec<u8> = Vec::new();
+    doc.save(&mut std::io::BufWriter::new(&mut bytes))
+        .map_err(|e| format!("Failed to render PDF: {}", e))?;
+
+    Ok(bytes)
+}
+
+// Write one character out to `path` in the requested export format
+fn export_character_to_path(
+    character: &Character,
+    format: ExportFormat,
+    path: &std::path::Path,
+) -> Result<(), String> {
+    match format {
+        ExportFormat::Json => {
+            let content = serde_json::to_string_pretty(character)
+                .map_err(|e| format!("Failed to serialize character: {}", e))?;
+            fs::write(path, content).map_err(|e| format!("Failed to write file: {}", e))
+        }
+        ExportFormat::Markdown => {
+            fs::write(path, character_to_markdown(character))
+                .map_err(|e| format!("Failed to write file: {}", e))
+        }
+        ExportFormat::Pdf => {
+            let bytes = character_to_pdf_bytes(character)?;
+            fs::write(path, bytes).map_err(|e| format!("Failed to write file: {}", e))
+        }
+        ExportFormat::FoundryVtt => {
+            let content = serde_json::to_string_pretty(&character_to_foundry_vtt(character))
+                .map_err(|e| format!("Failed to serialize actor: {}", e))?;
+            fs::write(path, content).map_err(|e| format!("Failed to write file: {}", e))
+        }
+    }
+}
+
+// Confirm `dir` has already been granted to the fs plugin (via
+// `register_export_scope`/a configured storage root) before we let a
+// `std::fs` write land there. `export_character`/`export_all` write through
+// raw `std::fs`, not the fs plugin's own JS-invoked commands, so the
+// plugin's scope is never consulted unless we check it ourselves here.
+fn ensure_export_dir_allowed(
+    app: &tauri::AppHandle,
+    dir: &std::path::Path,
+) -> Result<PathBuf, String> {
+    use tauri_plugin_fs::FsExt;
+
+    let canonical = dir
+        .canonicalize()
+        .map_err(|e| format!("Invalid export directory {}: {}", dir.display(), e))?;
+
+    if !app.fs_scope().is_allowed(&canonical) {
+        return Err(format!(
+            "{} is outside the allowed export scope; call register_export_scope first",
+            canonical.display()
+        ));
+    }
+
+    Ok(canonical)
+}
+
+// Export a single character to `dest` with no scope check. Used directly by
+// the CLI, which runs with the user's own filesystem permissions rather than
+// the webview's sandboxed ones; the Tauri command wraps this with
+// `ensure_export_dir_allowed` below.
+fn export_character_unchecked(id: &str, format: ExportFormat, dest: &str) -> Result<(), String> {
+    let character = find_character(id)?;
+    export_character_to_path(&character, format, std::path::Path::new(dest))
+}
+
+// Export a single character to the requested format
+#[tauri::command]
+fn export_character(
+    id: String,
+    format: ExportFormat,
+    dest: String,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let dest_path = PathBuf::from(&dest);
+    let parent = match dest_path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => std::path::Path::new("."),
+    };
+    let filename = dest_path
+        .file_name()
+        .ok_or_else(|| format!("Invalid destination path: {}", dest))?;
+
+    let allowed_dir = ensure_export_dir_allowed(&app, parent)?;
+    export_character_unchecked(
+        &id,
+        format,
+        allowed_dir.join(filename).to_string_lossy().as_ref(),
+    )
+}
+
+// Export every character in the collection into `dest_dir`, one file each,
+// with no scope check. Used directly by the CLI; see
+// `export_character_unchecked`.
+fn export_all_unchecked(format: ExportFormat, dest_dir: &std::path::Path) -> Result<(), String> {
+    for character in load_characters()? {
+        let filename = format!("{}.{}", character.id, format.extension());
+        export_character_to_path(&character, format, &dest_dir.join(filename))?;
+    }
+    Ok(())
+}
+
+// Export every character in the collection into `dest_dir`, one file each
+#[tauri::command]
+fn export_all(format: ExportFormat, dest_dir: String, app: tauri::AppHandle) -> Result<(), String> {
+    let allowed_dir = ensure_export_dir_allowed(&app, &PathBuf::from(&dest_dir))?;
+    export_all_unchecked(format, &allowed_dir)
+}
+
+// Headless CLI, letting power users script imports/exports without the GUI.
+// Subcommands call straight into the same functions the Tauri commands use,
+// so behavior never drifts between the two front ends.
+#[derive(clap::Parser)]
+#[command(name = "dndbeyond", about = "Manage D&D Beyond desktop characters from the command line")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+}
+
+#[derive(clap::Subcommand)]
+enum CliCommand {
+    /// List every stored character
+    List,
+    /// Export a character to a file
+    Export {
+        id: String,
+        #[arg(long)]
+        format: ExportFormat,
+        #[arg(long = "out")]
+        out: String,
+    },
+    /// Import a character from a D&D Beyond export file and save it
+    Import { file: String },
+    /// Delete a character by id
+    Delete { id: String },
+}
+
+fn run_cli(command: CliCommand) -> Result<(), String> {
+    match command {
+        CliCommand::List => {
+            for character in load_characters()? {
+                println!(
+                    "{}\t{} (level {} {})",
+                    character.id, character.name, character.level, character.class
+                );
+            }
+        }
+        CliCommand::Export { id, format, out } => {
+            export_character_unchecked(&id, format, &out)?;
+            println!("Exported {} to {}", id, out);
+        }
+        CliCommand::Import { file } => {
+            let json = fs::read_to_string(&file)
+                .map_err(|e| format!("Failed to read {}: {}", file, e))?;
+            let character = import_dndbeyond(json)?;
+            let id = character.id.clone();
+            save_character(character)?;
+            println!("Imported character {}", id);
+        }
+        CliCommand::Delete { id } => {
+            delete_character(id.clone())?;
+            println!("Deleted {}", id);
+        }
+    }
+    Ok(())
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_shell::init())
+        .setup(|app| {
+            register_storage_scope(&app.handle())
+                .map_err(Box::<dyn std::error::Error>::from)?;
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             load_characters,
             save_character,
             delete_character,
-            get_characters_directory
+            get_characters_directory,
+            get_settings,
+            set_storage_roots,
+            list_character_backups,
+            restore_character_backup,
+            import_dndbeyond,
+            export_character,
+            export_all,
+            register_export_scope
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
 
 fn main() {
+    use clap::error::ErrorKind;
+    use clap::{CommandFactory, Parser};
+
+    // `Cli::parse()` would hard-exit on any argument it doesn't recognize,
+    // but this is also the GUI entry point: a stray OS/launcher-injected arg
+    // (e.g. macOS's `-psn_...` when launched from Finder) must fall through
+    // to the GUI instead of killing the app with a clap usage error. A
+    // recognized subcommand with bad arguments (e.g. `export --format bogus`)
+    // is a different story: that's a genuine scripting mistake, and silently
+    // launching the GUI instead of reporting it would defeat automation.
+    match Cli::try_parse() {
+        Ok(cli) => {
+            if let Some(command) = cli.command {
+                if let Err(e) = run_cli(command) {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+                return;
+            }
+        }
+        Err(e) => {
+            let names_subcommand = std::env::args_os()
+                .nth(1)
+                .map_or(false, |arg| Cli::command().find_subcommand(&arg).is_some());
+            match e.kind() {
+                ErrorKind::InvalidSubcommand | ErrorKind::UnknownArgument if !names_subcommand => {}
+                _ => {
+                    let _ = e.print();
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+
     run();
 }